@@ -1,5 +1,8 @@
 use serde::Deserialize;
+use serde_json::Value;
 use std::fmt;
+use std::future::Future;
+use std::time::Duration;
 
 /// An error returned by the `axis` crate.
 ///
@@ -16,13 +19,24 @@ pub enum Error<TE> {
     UnparseableResponseError(UnparseableResponseError),
     /// The API call returned a structured error.
     ApiError(ApiError),
+    /// The API call returned an RFC 7807 `application/problem+json` error.
+    /// Produced by [`Error::from_problem_details_body`].
+    ProblemDetails(ProblemDetails),
     /// The device does not support this feature.
     UnsupportedFeature,
     /// An error which isn't yet properly itemized.
     Other(&'static str),
 }
 
-impl<TE: std::error::Error> std::error::Error for Error<TE> {}
+impl<TE: std::error::Error + 'static> std::error::Error for Error<TE> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::TransportError(te) => Some(te),
+            Error::UnparseableResponseError(e) => e.source(),
+            _ => None,
+        }
+    }
+}
 
 impl<TE: fmt::Display> fmt::Display for Error<TE> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -32,12 +46,55 @@ impl<TE: fmt::Display> fmt::Display for Error<TE> {
             Error::BadContentTypeError(ct) => write!(f, "bad content type: got {:?}", ct),
             Error::UnparseableResponseError(e) => write!(f, "unparseable response: {:?}", e),
             Error::ApiError(e) => write!(f, "JSON API error: {:?}", e),
+            Error::ProblemDetails(e) => write!(f, "problem details: {:?}", e),
             Error::UnsupportedFeature => write!(f, "this device does not support that feature"),
             Error::Other(e) => write!(f, "error: {}", e),
         }
     }
 }
 
+impl<TE> Error<TE> {
+    /// Whether this error represents a transient failure that is likely to
+    /// succeed if retried, as opposed to a permanent failure that will
+    /// simply fail again (e.g. `ApiError::AccessForbidden` or
+    /// `ApiError::InvalidParameter`).
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::TransportError(_) => true,
+            Error::BadStatusCodeError(sc) => sc.is_server_error(),
+            Error::ApiError(ApiError::InternalError) | Error::ApiError(ApiError::SystemBusy) => {
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Parses `body` as an RFC 7807 problem-details error when
+    /// `content_type` is `application/problem+json`, returning
+    /// `Error::ProblemDetails` rather than leaving the caller to fall
+    /// through to `UnparseableResponseError`.
+    ///
+    /// Returns `None` for any other content type, so the caller can
+    /// continue on to whatever the next parsing step is (e.g. the legacy
+    /// `{code, message}` JSON error body). If the content type matches but
+    /// the body doesn't parse as `ProblemDetails`, returns
+    /// `Some(Error::UnparseableResponseError(..))`.
+    pub fn from_problem_details_body(
+        content_type: Option<&http::header::HeaderValue>,
+        body: &[u8],
+    ) -> Option<Self> {
+        let content_type = content_type?.to_str().ok()?;
+        let mime = content_type.split(';').next().unwrap_or(content_type).trim();
+        if mime != "application/problem+json" {
+            return None;
+        }
+        Some(match serde_json::from_slice::<ProblemDetails>(body) {
+            Ok(problem_details) => Error::ProblemDetails(problem_details),
+            Err(e) => e.into(),
+        })
+    }
+}
+
 impl<TE> From<serde_json::Error> for Error<TE> {
     fn from(e: serde_json::Error) -> Self {
         Error::UnparseableResponseError(UnparseableResponseError::JsonDeError(e))
@@ -56,6 +113,37 @@ impl<TE> From<ApiError> for Error<TE> {
     }
 }
 
+impl<TE> From<ProblemDetails> for Error<TE> {
+    fn from(e: ProblemDetails) -> Self {
+        Error::ProblemDetails(e)
+    }
+}
+
+/// An RFC 7807 "problem details" error body, as returned by newer VAPIX
+/// endpoints with a content type of `application/problem+json`.
+///
+/// Parsed from a response by [`Error::from_problem_details_body`], which
+/// dispatches on the response's content type.
+///
+/// See <https://www.rfc-editor.org/rfc/rfc7807>.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct ProblemDetails {
+    /// A URI reference that identifies the problem type.
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    /// A short, human-readable summary of the problem type.
+    pub title: Option<String>,
+    /// The HTTP status code generated by the origin server.
+    pub status: Option<u16>,
+    /// A human-readable explanation specific to this occurrence of the problem.
+    pub detail: Option<String>,
+    /// A URI reference that identifies the specific occurrence of the problem.
+    pub instance: Option<String>,
+    /// Extension members beyond the standard RFC 7807 fields.
+    #[serde(flatten)]
+    pub extensions: serde_json::Map<String, Value>,
+}
+
 #[derive(Debug)]
 pub enum UnparseableResponseError {
     /// JSON deserialization failed.
@@ -64,15 +152,42 @@ pub enum UnparseableResponseError {
     XmlDeError(quick_xml::DeError),
 }
 
+impl fmt::Display for UnparseableResponseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UnparseableResponseError::JsonDeError(e) => {
+                write!(f, "JSON deserialization error: {}", e)
+            }
+            UnparseableResponseError::XmlDeError(e) => {
+                write!(f, "XML deserialization error: {}", e)
+            }
+        }
+    }
+}
+
+impl std::error::Error for UnparseableResponseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UnparseableResponseError::JsonDeError(e) => Some(e),
+            UnparseableResponseError::XmlDeError(e) => Some(e),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum ApiError {
     InvalidParameter,
+    ValueNotAccepted,
     AccessForbidden,
     UnsupportedHttpMethod,
     UnsupportedApiVersion,
     UnsupportedApiMethod,
+    ParameterValueOutOfRange,
     InvalidJsonFormat,
+    ParameterIsMissing,
     RequiredParameterIsMissing,
+    IllegalParameterValue,
+    SystemBusy,
     InternalError,
     OtherError(Box<RawJsonApiError>),
 }
@@ -81,18 +196,113 @@ impl From<RawJsonApiError> for ApiError {
     fn from(e: RawJsonApiError) -> Self {
         match e.code {
             1000 => ApiError::InvalidParameter,
+            2000 => ApiError::ValueNotAccepted,
             2001 => ApiError::AccessForbidden,
             2002 => ApiError::UnsupportedHttpMethod,
             2003 => ApiError::UnsupportedApiVersion,
             2004 => ApiError::UnsupportedApiMethod,
+            3000 => ApiError::ParameterValueOutOfRange,
             4000 => ApiError::InvalidJsonFormat,
+            4001 => ApiError::ParameterIsMissing,
             4002 => ApiError::RequiredParameterIsMissing,
+            4003 => ApiError::IllegalParameterValue,
+            5000 => ApiError::SystemBusy,
             8000 => ApiError::InternalError,
             _ => ApiError::OtherError(Box::new(e)),
         }
     }
 }
 
+/// A coarse category for an [`ApiError`], suitable for routing or logging
+/// without needing to match on every variant.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ErrorType {
+    /// The caller is not authorized to perform the request.
+    Auth,
+    /// The request itself was malformed or invalid.
+    InvalidRequest,
+    /// The device failed while processing an otherwise valid request.
+    Internal,
+    /// The device does not support the requested API, version, or method.
+    Unsupported,
+}
+
+impl ApiError {
+    /// A stable, machine-consumable identifier for this error, independent of
+    /// the underlying VAPIX numeric code.
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            ApiError::InvalidParameter => "invalid-parameter",
+            ApiError::ValueNotAccepted => "value-not-accepted",
+            ApiError::AccessForbidden => "access-forbidden",
+            ApiError::UnsupportedHttpMethod => "unsupported-http-method",
+            ApiError::UnsupportedApiVersion => "unsupported-api-version",
+            ApiError::UnsupportedApiMethod => "unsupported-api-method",
+            ApiError::ParameterValueOutOfRange => "parameter-value-out-of-range",
+            ApiError::InvalidJsonFormat => "invalid-json-format",
+            ApiError::ParameterIsMissing => "parameter-is-missing",
+            ApiError::RequiredParameterIsMissing => "required-parameter-is-missing",
+            ApiError::IllegalParameterValue => "illegal-parameter-value",
+            ApiError::SystemBusy => "system-busy",
+            ApiError::InternalError => "internal-error",
+            ApiError::OtherError(_) => "other-error",
+        }
+    }
+
+    /// The coarse category this error falls into.
+    pub fn error_type(&self) -> ErrorType {
+        match self {
+            ApiError::AccessForbidden => ErrorType::Auth,
+            ApiError::InvalidParameter
+            | ApiError::ValueNotAccepted
+            | ApiError::ParameterValueOutOfRange
+            | ApiError::InvalidJsonFormat
+            | ApiError::ParameterIsMissing
+            | ApiError::RequiredParameterIsMissing
+            | ApiError::IllegalParameterValue => ErrorType::InvalidRequest,
+            ApiError::UnsupportedHttpMethod
+            | ApiError::UnsupportedApiVersion
+            | ApiError::UnsupportedApiMethod => ErrorType::Unsupported,
+            ApiError::SystemBusy | ApiError::InternalError => ErrorType::Internal,
+            ApiError::OtherError(_) => ErrorType::Internal,
+        }
+    }
+
+    /// The HTTP status code a proxy sitting in front of the device should
+    /// surface for this error.
+    pub fn http_status(&self) -> http::StatusCode {
+        match self {
+            ApiError::AccessForbidden => http::StatusCode::FORBIDDEN,
+            ApiError::InvalidParameter
+            | ApiError::ValueNotAccepted
+            | ApiError::ParameterValueOutOfRange
+            | ApiError::InvalidJsonFormat
+            | ApiError::ParameterIsMissing
+            | ApiError::RequiredParameterIsMissing
+            | ApiError::IllegalParameterValue => http::StatusCode::BAD_REQUEST,
+            ApiError::UnsupportedHttpMethod => http::StatusCode::METHOD_NOT_ALLOWED,
+            ApiError::UnsupportedApiVersion | ApiError::UnsupportedApiMethod => {
+                http::StatusCode::NOT_IMPLEMENTED
+            }
+            ApiError::SystemBusy => http::StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::InternalError | ApiError::OtherError(_) => {
+                http::StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
+    /// A link to the VAPIX documentation section describing this error, if
+    /// one is known.
+    pub fn help_url(&self) -> Option<&'static str> {
+        const GENERAL_ERRORS_DOC: &str =
+            "https://www.axis.com/vapix-library/#/subjects/t10175981/section/t10036126";
+        match self {
+            ApiError::OtherError(_) => None,
+            _ => Some(GENERAL_ERRORS_DOC),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct RawJsonApiError {
@@ -106,8 +316,57 @@ impl<TE> From<RawJsonApiError> for Error<TE> {
     }
 }
 
-pub(crate) trait ResultExt {
+/// A truncated exponential backoff policy with full jitter, for retrying
+/// transient errors.
+///
+/// On attempt `n` (0-indexed), the policy sleeps for a random duration in
+/// `[0, min(cap, base * 2^n)]` before the next try. See
+/// <https://aws.amazon.com/blogs/architecture/exponential-backoff-and-jitter/>.
+///
+/// The sleep mechanism is pluggable so this stays runtime-agnostic: pass in
+/// e.g. `tokio::time::sleep` or `async_std::task::sleep`.
+pub struct RetryPolicy<Sleep> {
+    base: Duration,
+    cap: Duration,
+    max_retries: u32,
+    sleep: Sleep,
+}
+
+impl<Sleep, SleepFut> RetryPolicy<Sleep>
+where
+    Sleep: Fn(Duration) -> SleepFut,
+    SleepFut: Future<Output = ()>,
+{
+    pub fn new(base: Duration, cap: Duration, max_retries: u32, sleep: Sleep) -> Self {
+        RetryPolicy {
+            base,
+            cap,
+            max_retries,
+            sleep,
+        }
+    }
+
+    fn backoff_for_attempt(&self, attempt: u32) -> Duration {
+        let exp = self.base.saturating_mul(1u32.checked_shl(attempt).unwrap_or(u32::MAX));
+        exp.min(self.cap).mul_f64(rand::random::<f64>())
+    }
+}
+
+pub(crate) trait ResultExt: Sized {
     fn map_404_to_unsupported_feature(self) -> Self;
+
+    /// Retries `op` according to `policy` as long as this result (and each
+    /// subsequent attempt) is a transient error, per [`Error::is_transient`].
+    async fn retry_transient<F, Fut, Sleep, SleepFut>(
+        self,
+        op: F,
+        policy: &RetryPolicy<Sleep>,
+    ) -> Self
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Self>,
+        Sleep: Fn(Duration) -> SleepFut,
+        SleepFut: Future<Output = ()>;
 }
 
 impl<T, TE> ResultExt for std::result::Result<T, Error<TE>> {
@@ -119,4 +378,103 @@ impl<T, TE> ResultExt for std::result::Result<T, Error<TE>> {
             other => other,
         }
     }
+
+    async fn retry_transient<F, Fut, Sleep, SleepFut>(
+        self,
+        mut op: F,
+        policy: &RetryPolicy<Sleep>,
+    ) -> Self
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Self>,
+        Sleep: Fn(Duration) -> SleepFut,
+        SleepFut: Future<Output = ()>,
+    {
+        let mut result = self;
+        let mut attempt = 0;
+        while let Err(ref e) = result {
+            if !e.is_transient() || attempt >= policy.max_retries {
+                break;
+            }
+            (policy.sleep)(policy.backoff_for_attempt(attempt)).await;
+            attempt += 1;
+            result = op().await;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn problem_details_content_type_is_parsed() {
+        let content_type = http::header::HeaderValue::from_static("application/problem+json");
+        let body = br#"{"type":"https://example.com/probs/out-of-credit","title":"Out of credit","status":403,"balance":30}"#;
+        let err = Error::<std::io::Error>::from_problem_details_body(Some(&content_type), body)
+            .expect("content type should be recognized");
+        match err {
+            Error::ProblemDetails(pd) => {
+                assert_eq!(pd.type_.as_deref(), Some("https://example.com/probs/out-of-credit"));
+                assert_eq!(pd.title.as_deref(), Some("Out of credit"));
+                assert_eq!(pd.status, Some(403));
+                assert_eq!(pd.extensions.get("balance").and_then(|v| v.as_i64()), Some(30));
+            }
+            other => panic!("expected ProblemDetails, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn non_problem_details_content_type_is_not_parsed() {
+        let content_type = http::header::HeaderValue::from_static("application/json");
+        let body = br#"{"code": 1000, "message": "bad"}"#;
+        assert!(Error::<std::io::Error>::from_problem_details_body(Some(&content_type), body).is_none());
+    }
+
+    #[test]
+    fn missing_content_type_is_not_parsed() {
+        let body = br#"{"type":"https://example.com/probs/out-of-credit"}"#;
+        assert!(Error::<std::io::Error>::from_problem_details_body(None, body).is_none());
+    }
+
+    #[test]
+    fn malformed_problem_details_body_is_unparseable_not_a_panic() {
+        let content_type = http::header::HeaderValue::from_static("application/problem+json");
+        let body = b"not json";
+        match Error::<std::io::Error>::from_problem_details_body(Some(&content_type), body) {
+            Some(Error::UnparseableResponseError(_)) => {}
+            other => panic!("expected UnparseableResponseError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn backoff_for_attempt_stays_within_the_jittered_range() {
+        let policy = RetryPolicy::new(
+            Duration::from_millis(100),
+            Duration::from_secs(10),
+            5,
+            |_| async {},
+        );
+        for attempt in 0..10 {
+            let backoff = policy.backoff_for_attempt(attempt);
+            let exp = Duration::from_millis(100).saturating_mul(1u32.checked_shl(attempt).unwrap_or(u32::MAX));
+            let max = exp.min(Duration::from_secs(10));
+            assert!(backoff <= max, "attempt {attempt}: {backoff:?} > {max:?}");
+        }
+    }
+
+    #[test]
+    fn backoff_for_attempt_is_truncated_by_the_cap() {
+        let policy = RetryPolicy::new(
+            Duration::from_secs(1),
+            Duration::from_millis(500),
+            5,
+            |_| async {},
+        );
+        // base * 2^10 vastly exceeds the cap, so every sample must be capped.
+        for _ in 0..20 {
+            assert!(policy.backoff_for_attempt(10) <= Duration::from_millis(500));
+        }
+    }
 }
\ No newline at end of file
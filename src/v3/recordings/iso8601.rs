@@ -15,29 +15,171 @@ where
 pub fn deserialize<'de, D>(
     deserializer: D,
 ) -> Result<DateTime<FixedOffset>, <D as Deserializer<'de>>::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_with_offset_secs::<0, D>(deserializer)
+}
+
+/// Deserializes a timestamp that may arrive without a zone (local device
+/// time), attaching the offset `OFFSET_SECS` (east of UTC) in that case.
+///
+/// Axis devices in the field emit timestamps in several shapes: RFC 3339
+/// with an explicit offset, UTC with a trailing `Z`, ISO 8601 with no zone
+/// at all (e.g. `2023-04-01T12:30:00`), and occasionally Unix epoch seconds
+/// (or milliseconds, as a 13-digit integer) as a bare number. This tries
+/// each in turn and normalizes the result to `DateTime<FixedOffset>`.
+///
+/// A runtime-configurable offset (e.g. a `with_default_offset(FixedOffset)`
+/// constructor) can't be threaded through `#[serde(with = ...)]`: that
+/// attribute, like `deserialize_with`, only accepts a path expression, not a
+/// function call, so there is no way to pass it an argument computed at
+/// runtime. The offset is threaded through as a const generic instead, and
+/// used via `deserialize_with`, not `with`:
+///
+/// ```ignore
+/// #[derive(Deserialize)]
+/// struct DeviceEvent {
+///     #[serde(deserialize_with = "iso8601::deserialize_with_offset_secs::<3600, _>")]
+///     timestamp: DateTime<FixedOffset>,
+/// }
+/// ```
+pub fn deserialize_with_offset_secs<'de, const OFFSET_SECS: i32, D>(
+    deserializer: D,
+) -> Result<DateTime<FixedOffset>, <D as Deserializer<'de>>::Error>
 where
     D: Deserializer<'de>,
 {
     use serde::de::{self, Visitor};
     use std::fmt;
 
-    struct V;
+    let default_offset =
+        FixedOffset::east_opt(OFFSET_SECS).ok_or_else(|| de::Error::custom("invalid offset"))?;
+
+    struct V(FixedOffset);
     impl<'de> Visitor<'de> for V {
         type Value = DateTime<FixedOffset>;
 
         fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            f.write_str("an ISO8601 timestamp")
+            f.write_str("an ISO8601 timestamp or Unix epoch seconds/milliseconds")
         }
 
         fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
         where
             E: de::Error,
         {
-            DateTime::parse_from_rfc3339(v)
-                .map_err(|e| E::custom(format!("invaild timestamp {:?}: {}", v, e)))
+            parse_timestamp(v, self.0)
+                .map_err(|e| E::custom(format!("invalid timestamp {:?}: {}", v, e)))
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            epoch_to_date_time(v).map_err(|e| E::custom(format!("invalid timestamp {}: {}", v, e)))
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            let v = i64::try_from(v)
+                .map_err(|_| E::custom(format!("timestamp {} is out of range", v)))?;
+            epoch_to_date_time(v).map_err(|e| E::custom(format!("invalid timestamp {}: {}", v, e)))
         }
     }
 
-    let visitor = V;
-    deserializer.deserialize_str(visitor)
+    deserializer.deserialize_any(V(default_offset))
+}
+
+/// Parses a Unix epoch timestamp, interpreting 13+ digit values as
+/// milliseconds and anything shorter as seconds.
+fn epoch_to_date_time(v: i64) -> Result<DateTime<FixedOffset>, String> {
+    let utc = if v.unsigned_abs().to_string().len() >= 13 {
+        Utc.timestamp_millis_opt(v)
+    } else {
+        Utc.timestamp_opt(v, 0)
+    };
+    utc.single()
+        .map(|dt| dt.fixed_offset())
+        .ok_or_else(|| format!("{} is out of range for a Unix epoch timestamp", v))
+}
+
+fn parse_timestamp(v: &str, default_offset: FixedOffset) -> Result<DateTime<FixedOffset>, String> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(v) {
+        return Ok(dt);
+    }
+
+    // `%.f` matches zero or more fractional digits, so this also covers
+    // timestamps with no fractional seconds at all.
+    if let Ok(naive) = NaiveDateTime::parse_from_str(v, "%Y-%m-%dT%H:%M:%S%.f") {
+        return default_offset
+            .from_local_datetime(&naive)
+            .single()
+            .ok_or_else(|| "ambiguous local time".to_string());
+    }
+
+    if let Ok(epoch) = v.parse::<i64>() {
+        return epoch_to_date_time(epoch);
+    }
+
+    Err("does not match any known timestamp format".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc() -> FixedOffset {
+        FixedOffset::east_opt(0).unwrap()
+    }
+
+    #[test]
+    fn parses_rfc3339_with_offset() {
+        let dt = parse_timestamp("2023-04-01T12:30:00+02:00", utc()).unwrap();
+        assert_eq!(dt.to_rfc3339(), "2023-04-01T12:30:00+02:00");
+    }
+
+    #[test]
+    fn parses_rfc3339_with_trailing_z() {
+        let dt = parse_timestamp("2023-04-01T12:30:00Z", utc()).unwrap();
+        assert_eq!(dt.to_rfc3339(), "2023-04-01T12:30:00+00:00");
+    }
+
+    #[test]
+    fn parses_zoneless_timestamp_with_default_offset() {
+        let offset = FixedOffset::east_opt(3600).unwrap();
+        let dt = parse_timestamp("2023-04-01T12:30:00", offset).unwrap();
+        assert_eq!(dt.to_rfc3339(), "2023-04-01T12:30:00+01:00");
+    }
+
+    #[test]
+    fn parses_zoneless_timestamp_with_fractional_seconds() {
+        let offset = FixedOffset::east_opt(3600).unwrap();
+        let dt = parse_timestamp("2023-04-01T12:30:00.5", offset).unwrap();
+        assert_eq!(dt.to_rfc3339(), "2023-04-01T12:30:00.500+01:00");
+    }
+
+    #[test]
+    fn parses_epoch_seconds() {
+        let dt = parse_timestamp("1700000000", utc()).unwrap();
+        assert_eq!(dt.to_rfc3339(), "2023-11-14T22:13:20+00:00");
+    }
+
+    #[test]
+    fn parses_epoch_milliseconds() {
+        let dt = parse_timestamp("1700000000000", utc()).unwrap();
+        assert_eq!(dt.to_rfc3339(), "2023-11-14T22:13:20+00:00");
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(parse_timestamp("not a timestamp", utc()).is_err());
+    }
+
+    #[test]
+    fn epoch_out_of_range_errors_instead_of_panicking() {
+        assert!(epoch_to_date_time(i64::MAX).is_err());
+        assert!(epoch_to_date_time(i64::MIN).is_err());
+    }
 }